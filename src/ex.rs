@@ -1,16 +1,21 @@
 use config::Config;
-use crates::{Crate, RegistryCrate};
+use crates::{Crate, GitHubRepo, RegistryCrate};
 use dirs::{self, EXPERIMENT_DIR, TEST_SOURCE_DIR};
 use errors::*;
 use file;
 use git;
 use lists::{self, List};
-use results::WriteResults;
+use rayon;
+use rayon::prelude::*;
+use results::{ReadResults, TestResult, WriteResults};
 use run::RunCommand;
 use serde_json;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toml;
 use toml_frobber;
 use toolchain::{self, CargoState, Toolchain};
 use util;
@@ -22,12 +27,40 @@ string_enum!(pub enum ExMode {
     UnstableFeatures => "unstable-features",
 });
 
-string_enum!(pub enum ExCrateSelect {
-    Full => "full",
-    Demo => "demo",
-    SmallRandom => "small-random",
-    Top100 => "top-100",
-});
+#[derive(Debug, Clone)]
+pub enum ExCrateSelect {
+    Full,
+    Demo,
+    SmallRandom,
+    Top100,
+    File(PathBuf),
+}
+
+impl FromStr for ExCrateSelect {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "full" => ExCrateSelect::Full,
+            "demo" => ExCrateSelect::Demo,
+            "small-random" => ExCrateSelect::SmallRandom,
+            "top-100" => ExCrateSelect::Top100,
+            s => bail!("invalid crate select: {}", s),
+        })
+    }
+}
+
+impl fmt::Display for ExCrateSelect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExCrateSelect::Full => write!(f, "full"),
+            ExCrateSelect::Demo => write!(f, "demo"),
+            ExCrateSelect::SmallRandom => write!(f, "small-random"),
+            ExCrateSelect::Top100 => write!(f, "top-100"),
+            ExCrateSelect::File(ref path) => write!(f, "{}", path.display()),
+        }
+    }
+}
 
 string_enum!(pub enum ExCapLints {
     Allow => "allow",
@@ -52,6 +85,14 @@ pub struct Experiment {
     pub mode: ExMode,
     pub cap_lints: ExCapLints,
     pub rustflags: Option<String>,
+    #[serde(default)]
+    pub ignore: HashSet<String>,
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+}
+
+fn default_threads() -> usize {
+    1
 }
 
 pub struct ExOpts {
@@ -61,6 +102,8 @@ pub struct ExOpts {
     pub crates: ExCrateSelect,
     pub cap_lints: ExCapLints,
     pub rustflags: Option<String>,
+    pub ignore: HashSet<String>,
+    pub threads: usize,
 }
 
 pub fn get_crates(crates: ExCrateSelect, config: &Config) -> Result<Vec<Crate>> {
@@ -69,9 +112,52 @@ pub fn get_crates(crates: ExCrateSelect, config: &Config) -> Result<Vec<Crate>>
         ExCrateSelect::Demo => demo_list(config),
         ExCrateSelect::SmallRandom => small_random(),
         ExCrateSelect::Top100 => top_100(),
+        ExCrateSelect::File(ref path) => read_crate_list_file(path),
     }
 }
 
+#[derive(Deserialize)]
+struct CrateListFile {
+    crates: BTreeMap<String, CrateListEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CrateListEntry {
+    Registry { name: String, version: String },
+    GitHub { github: String },
+    Local { path: PathBuf },
+}
+
+fn read_crate_list_file(path: &Path) -> Result<Vec<Crate>> {
+    let contents =
+        file::read_string(path).chain_err(|| format!("failed to read crate list {}", path.display()))?;
+    let list: CrateListFile = toml::from_str(&contents)
+        .chain_err(|| format!("failed to parse crate list {}", path.display()))?;
+
+    list.crates
+        .into_iter()
+        .map(|(label, entry)| match entry {
+            CrateListEntry::Registry { name, version } => {
+                Ok(Crate::Registry(RegistryCrate { name, version }))
+            }
+            CrateListEntry::GitHub { github } => {
+                let mut parts = github.splitn(2, '/');
+                let org = parts.next();
+                let name = parts.next();
+                match (org, name) {
+                    (Some(org), Some(name)) => Ok(Crate::GitHub(GitHubRepo {
+                        org: org.to_string(),
+                        name: name.to_string(),
+                    })),
+                    _ => bail!("invalid github repo '{}' for crate '{}'", github, label),
+                }
+            }
+            CrateListEntry::Local { path } => Ok(Crate::Local(path)),
+        })
+        .collect()
+}
+
 pub fn define(opts: ExOpts, config: &Config) -> Result<()> {
     delete(&opts.name)?;
     define_(
@@ -81,6 +167,8 @@ pub fn define(opts: ExOpts, config: &Config) -> Result<()> {
         opts.mode,
         opts.cap_lints,
         opts.rustflags,
+        opts.ignore,
+        opts.threads,
     )
 }
 
@@ -106,6 +194,8 @@ pub fn demo_list(config: &Config) -> Result<Vec<Crate>> {
 
                 found
             }
+            // The global crate lists never contain local-path crates.
+            Crate::Local(_) => false,
         })
         .collect::<Vec<_>>();
 
@@ -134,6 +224,16 @@ fn top_100() -> Result<Vec<Crate>> {
     Ok(crates)
 }
 
+/// Returns the name an `--ignore` entry is matched against: the registry
+/// crate's name, the GitHub repo's `owner/repo` slug, or the local path.
+fn ignore_key(krate: &Crate) -> String {
+    match *krate {
+        Crate::Registry(RegistryCrate { ref name, .. }) => name.clone(),
+        Crate::GitHub(ref repo) => repo.slug(),
+        Crate::Local(ref path) => path.display().to_string(),
+    }
+}
+
 pub fn define_(
     ex_name: &str,
     toolchains: Vec<Toolchain>,
@@ -141,22 +241,31 @@ pub fn define_(
     mode: ExMode,
     cap_lints: ExCapLints,
     rustflags: Option<String>,
+    ignore: HashSet<String>,
+    threads: usize,
 ) -> Result<()> {
     info!(
         "defining experiment {} for {} crates",
         ex_name,
         crates.len()
     );
-    let ex = Experiment {
+    let mut ex = Experiment {
         name: ex_name.to_string(),
         crates,
         toolchains,
         mode,
         cap_lints,
         rustflags,
+        ignore,
+        threads,
     };
 
+    // Validate against the full cohort first, so the ignore-entries-matching-
+    // nothing warning in `validate()` has something to match against, then
+    // drop the ignored crates for good.
     ex.validate()?;
+    let ignore = ex.ignore.clone();
+    ex.crates.retain(|krate| !ignore.contains(&ignore_key(krate)));
 
     fs::create_dir_all(&ex_dir(&ex.name))?;
     let json = serde_json::to_string(&ex)?;
@@ -167,6 +276,17 @@ pub fn define_(
 
 impl Experiment {
     pub fn validate(&self) -> Result<()> {
+        for unused in self
+            .ignore
+            .iter()
+            .filter(|entry| !self.crates.iter().any(|krate| ignore_key(krate) == **entry))
+        {
+            warn!(
+                "ignore entry '{}' does not match any crate in this experiment's cohort",
+                unused
+            );
+        }
+
         if self.toolchains[0] == self.toolchains[1] {
             bail!("reusing the same toolchain isn't supported");
         }
@@ -188,15 +308,31 @@ impl Experiment {
     }
 
     pub fn fetch_repo_crates(&self) -> Result<()> {
-        for repo in self.crates.iter().filter_map(|krate| krate.github()) {
-            if let Err(e) = git::shallow_clone_or_pull(&repo.url(), &repo.mirror_dir()) {
-                util::report_error(&e);
-            }
-        }
-        Ok(())
+        with_thread_pool(self.threads, || {
+            self.crates
+                .par_iter()
+                .filter_map(|krate| krate.github())
+                .for_each(|repo| {
+                    if let Err(e) = git::shallow_clone_or_pull(&repo.url(), &repo.mirror_dir()) {
+                        util::report_error(&e);
+                    }
+                });
+        })
     }
 }
 
+fn with_thread_pool<F, R>(threads: usize, f: F) -> Result<R>
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .chain_err(|| "failed to build worker pool")?;
+    Ok(pool.install(f))
+}
+
 impl Experiment {
     pub fn load(ex_name: &str) -> Result<Self> {
         let config = file::read_string(&config_file(ex_name))?;
@@ -205,46 +341,254 @@ impl Experiment {
 }
 
 #[cfg_attr(feature = "cargo-clippy", allow(match_ref_pats))]
-pub fn frob_toml(ex: &Experiment, tc: &Toolchain, krate: &Crate) -> Result<()> {
-    if let Crate::Registry(_) = *krate {
-        toml_frobber::frob_toml(&dirs::ex_crate_source(ex, tc, krate), krate)?;
+pub fn frob_toml(
+    ex: &Experiment,
+    tc: &Toolchain,
+    krate: &Crate,
+    allow_source_changes: bool,
+) -> Result<()> {
+    match *krate {
+        Crate::Registry(_) => {
+            toml_frobber::frob_toml(&dirs::ex_crate_source(ex, tc, krate), krate)?;
+        }
+        // Local crates have no separate managed copy, so frobbing it would mean
+        // writing straight into the user's real checkout. Like `with_work_crate`'s
+        // `Crate::Local` branch below, only do that when source changes are
+        // explicitly allowed; otherwise leave the crate's Cargo.toml alone.
+        Crate::Local(ref path) if allow_source_changes => {
+            toml_frobber::frob_toml(path, krate)?;
+        }
+        Crate::Local(_) | Crate::GitHub(_) => {}
     }
 
     Ok(())
 }
 
+fn capture_local_sha(path: &Path) -> Option<String> {
+    let (stdout, _) = RunCommand::new("git", &["rev-parse", "HEAD"])
+        .cd(path)
+        .run_capture()
+        .ok()?;
+    let shaline = stdout.get(0)?;
+    if shaline.is_empty() {
+        return None;
+    }
+    Some(shaline.to_string())
+}
+
 pub fn capture_shas<DB: WriteResults>(ex: &Experiment, crates: &[Crate], db: &DB) -> Result<()> {
-    for krate in crates {
-        if let Crate::GitHub(ref repo) = *krate {
-            let dir = repo.mirror_dir();
-            let r = RunCommand::new("git", &["rev-parse", "HEAD"])
-                .cd(&dir)
-                .run_capture();
-
-            let sha = match r {
-                Ok((stdout, _)) => if let Some(shaline) = stdout.get(0) {
-                    if !shaline.is_empty() {
-                        info!("sha for GitHub repo {}: {}", repo.slug(), shaline);
-                        shaline.to_string()
-                    } else {
-                        bail!("bogus output from git log for {}", dir.display());
-                    }
-                } else {
-                    bail!("bogus output from git log for {}", dir.display());
-                },
-                Err(e) => {
-                    bail!("unable to capture sha for {}: {}", dir.display(), e);
-                }
-            };
+    // Capturing each sha only touches the crate's own checkout/mirror dir, so
+    // it's safe to fan both kinds out; the results are collected and written
+    // to `db` afterwards, one crate at a time on this thread, rather than
+    // concurrently. A single crate failing to capture its sha (e.g. an
+    // unclonable mirror) shouldn't discard every other crate's already-
+    // computed sha, so errors are reported per-crate rather than aborting the
+    // whole collection.
+    let (local_shas, github_shas): (Vec<(&Crate, String)>, Vec<(&GitHubRepo, String)>) =
+        with_thread_pool(ex.threads, || {
+            let local_shas = crates
+                .par_iter()
+                .filter_map(|krate| match *krate {
+                    Crate::Local(ref path) => match capture_local_sha(path) {
+                        Some(sha) => {
+                            info!("sha for local crate {}: {}", krate, sha);
+                            Some((krate, sha))
+                        }
+                        None => {
+                            info!(
+                                "local crate {} isn't a git checkout, skipping sha capture",
+                                krate
+                            );
+                            None
+                        }
+                    },
+                    _ => None,
+                })
+                .collect();
 
-            db.record_sha(ex, repo, &sha)
-                .chain_err(|| format!("failed to record the sha of GitHub repo {}", repo.slug()))?;
-        }
+            let github_shas = crates
+                .par_iter()
+                .filter_map(|krate| krate.github())
+                .filter_map(|repo| {
+                    let dir = repo.mirror_dir();
+                    let r = RunCommand::new("git", &["rev-parse", "HEAD"])
+                        .cd(&dir)
+                        .run_capture();
+
+                    let sha = match r {
+                        Ok((stdout, _)) => if let Some(shaline) = stdout.get(0) {
+                            if !shaline.is_empty() {
+                                info!("sha for GitHub repo {}: {}", repo.slug(), shaline);
+                                Some(shaline.to_string())
+                            } else {
+                                None
+                            }
+                        } else {
+                            None
+                        },
+                        Err(_) => None,
+                    };
+
+                    let sha = match sha {
+                        Some(sha) => sha,
+                        None => {
+                            util::report_error(&format!(
+                                "unable to capture sha for {}",
+                                dir.display()
+                            ).into());
+                            return None;
+                        }
+                    };
+
+                    Some((repo, sha))
+                })
+                .collect();
+
+            (local_shas, github_shas)
+        })?;
+
+    for (krate, sha) in local_shas {
+        db.record_local_sha(ex, krate, &sha)
+            .chain_err(|| format!("failed to record the sha of local crate {}", krate))?;
+    }
+
+    for (repo, sha) in github_shas {
+        db.record_sha(ex, repo, &sha)
+            .chain_err(|| format!("failed to record the sha of GitHub repo {}", repo.slug()))?;
     }
 
     Ok(())
 }
 
+string_enum!(pub enum DiagnosticLevel {
+    Error => "error",
+    Warning => "warning",
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub code: Option<String>,
+    pub message: String,
+}
+
+fn parse_diagnostics(json_lines: &[String]) -> Vec<Diagnostic> {
+    json_lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|msg| msg["reason"] == "compiler-message")
+        .filter_map(|msg| {
+            let message = &msg["message"];
+            let level = message["level"].as_str()?.parse::<DiagnosticLevel>().ok()?;
+            let code = message["code"]["code"].as_str().map(str::to_string);
+            let rendered = message["rendered"]
+                .as_str()
+                .or_else(|| message["message"].as_str())
+                .unwrap_or_default()
+                .to_string();
+
+            Some(Diagnostic {
+                level,
+                code,
+                message: rendered,
+            })
+        })
+        .collect()
+}
+
+pub fn run_diagnostics(
+    ex: &Experiment,
+    toolchain: &Toolchain,
+    path: &Path,
+    args: &[&str],
+) -> Result<Vec<Diagnostic>> {
+    // cargo only accepts `--message-format` after the subcommand, so it has
+    // to follow `args` rather than precede it.
+    let mut full_args = args.to_vec();
+    full_args.push("--message-format=json");
+
+    let json_lines = toolchain
+        .run_cargo_diagnostics(ex, path, &full_args, CargoState::Unlocked)
+        .chain_err(|| "failed to run cargo with --message-format=json")?;
+
+    Ok(parse_diagnostics(&json_lines))
+}
+
+pub fn capture_diagnostics<DB: WriteResults>(
+    ex: &Experiment,
+    toolchain: &Toolchain,
+    krate: &Crate,
+    db: &DB,
+) -> Result<()> {
+    let diagnostics = with_work_crate(ex, toolchain, krate, false, |path| {
+        run_diagnostics(ex, toolchain, path, &["check", "--frozen"])
+    })?;
+
+    db.record_diagnostics(ex, toolchain, krate, &diagnostics)
+        .chain_err(|| format!("failed to record diagnostics for {}", krate))
+}
+
+#[derive(Debug, Clone, Default)]
+struct DiagnosticCounts {
+    by_code: BTreeMap<String, usize>,
+}
+
+impl<'a> From<&'a [Diagnostic]> for DiagnosticCounts {
+    fn from(diagnostics: &'a [Diagnostic]) -> Self {
+        let mut counts = DiagnosticCounts::default();
+        for d in diagnostics {
+            if let Some(ref code) = d.code {
+                *counts.by_code.entry(code.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiagnosticDelta {
+    pub code: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl DiagnosticDelta {
+    pub fn is_new(&self) -> bool {
+        self.before == 0 && self.after > 0
+    }
+}
+
+pub fn diff_diagnostics(before: &[Diagnostic], after: &[Diagnostic]) -> Vec<DiagnosticDelta> {
+    let before_counts = DiagnosticCounts::from(before);
+    let after_counts = DiagnosticCounts::from(after);
+
+    let mut codes = before_counts
+        .by_code
+        .keys()
+        .chain(after_counts.by_code.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    codes.sort();
+
+    codes
+        .into_iter()
+        .filter_map(|code| {
+            let before = *before_counts.by_code.get(code).unwrap_or(&0);
+            let after = *after_counts.by_code.get(code).unwrap_or(&0);
+            if before == after {
+                None
+            } else {
+                Some(DiagnosticDelta {
+                    code: code.clone(),
+                    before,
+                    after,
+                })
+            }
+        })
+        .collect()
+}
+
 fn crate_work_dir(ex: &Experiment, toolchain: &Toolchain, krate: &Crate) -> PathBuf {
     TEST_SOURCE_DIR
         .join(&ex.name)
@@ -252,6 +596,30 @@ fn crate_work_dir(ex: &Experiment, toolchain: &Toolchain, krate: &Crate) -> Path
         .join(krate.id())
 }
 
+/// Removes a symlink without following it into its target, unlike
+/// `util::remove_dir_all` which is only safe to use on a real copy.
+#[cfg(unix)]
+fn remove_symlink(path: &Path) -> Result<()> {
+    fs::remove_file(path).chain_err(|| format!("failed to remove symlink {}", path.display()))
+}
+
+#[cfg(windows)]
+fn remove_symlink(path: &Path) -> Result<()> {
+    fs::remove_dir(path).chain_err(|| format!("failed to remove symlink {}", path.display()))
+}
+
+#[cfg(unix)]
+fn make_symlink(src: &Path, dest: &Path) -> Result<()> {
+    ::std::os::unix::fs::symlink(src, dest)
+        .chain_err(|| format!("failed to symlink {} to {}", src.display(), dest.display()))
+}
+
+#[cfg(windows)]
+fn make_symlink(src: &Path, dest: &Path) -> Result<()> {
+    ::std::os::windows::fs::symlink_dir(src, dest)
+        .chain_err(|| format!("failed to symlink {} to {}", src.display(), dest.display()))
+}
+
 pub fn with_work_crate<F, R>(
     ex: &Experiment,
     toolchain: &Toolchain,
@@ -262,6 +630,37 @@ pub fn with_work_crate<F, R>(
 where
     F: Fn(&Path) -> Result<R>,
 {
+    if let Crate::Local(ref path) = *krate {
+        let dest_dir = crate_work_dir(ex, toolchain, krate);
+
+        if allow_source_changes {
+            info!(
+                "symlinking local crate {} into {}",
+                krate,
+                dest_dir.display()
+            );
+            if let Some(parent) = dest_dir.parent() {
+                fs::create_dir_all(parent).chain_err(|| {
+                    format!("failed to create directory {}", parent.display())
+                })?;
+            }
+            make_symlink(path, &dest_dir)?;
+            let r = f(&dest_dir);
+            remove_symlink(&dest_dir)?;
+            return r;
+        } else {
+            info!(
+                "copying local crate {} into {}",
+                krate,
+                dest_dir.display()
+            );
+            util::copy_dir(path, &dest_dir)?;
+            let r = f(&dest_dir);
+            util::remove_dir_all(&dest_dir)?;
+            return r;
+        }
+    }
+
     let src_dir = dirs::ex_crate_source(ex, toolchain, krate);
 
     if allow_source_changes {
@@ -322,12 +721,43 @@ pub fn fetch_crate_deps(ex: &Experiment, toolchain: &Toolchain, krate: &Crate) -
     })
 }
 
-pub fn prepare_all_toolchains(ex: &Experiment) -> Result<()> {
-    for tc in &ex.toolchains {
-        tc.prepare()?;
-    }
+pub fn capture_lockfiles(
+    config: &Config,
+    ex: &Experiment,
+    toolchain: &Toolchain,
+    crates: &[Crate],
+) -> Result<()> {
+    with_thread_pool(ex.threads, || {
+        crates.par_iter().for_each(|krate| {
+            if let Err(e) = capture_lockfile(config, ex, toolchain, krate) {
+                util::report_error(&e);
+            }
+        });
+    })
+}
 
-    Ok(())
+pub fn fetch_all_crate_deps(
+    ex: &Experiment,
+    toolchain: &Toolchain,
+    crates: &[Crate],
+) -> Result<()> {
+    with_thread_pool(ex.threads, || {
+        crates.par_iter().for_each(|krate| {
+            if let Err(e) = fetch_crate_deps(ex, toolchain, krate) {
+                util::report_error(&e);
+            }
+        });
+    })
+}
+
+pub fn prepare_all_toolchains(ex: &Experiment) -> Result<()> {
+    with_thread_pool(ex.threads, || -> Result<()> {
+        ex.toolchains
+            .par_iter()
+            .map(Toolchain::prepare)
+            .collect::<Result<Vec<()>>>()?;
+        Ok(())
+    })?
 }
 
 pub fn copy(ex1_name: &str, ex2_name: &str) -> Result<()> {
@@ -345,6 +775,134 @@ pub fn copy(ex1_name: &str, ex2_name: &str) -> Result<()> {
     util::copy_dir(ex1_dir, ex2_dir)
 }
 
+/// The outcome of reconciling two experiments' crate lists by identity
+/// (registry name, GitHub slug, or local path): crates only in one or the
+/// other, or a registry crate pinned to a different version in each.
+#[derive(Debug, Clone)]
+pub enum CrateDiff {
+    Added(Crate),
+    Removed(Crate),
+    VersionChanged {
+        name: String,
+        before: String,
+        after: String,
+    },
+}
+
+fn diff_crate_lists(ex1: &Experiment, ex2: &Experiment) -> Vec<CrateDiff> {
+    let before: BTreeMap<String, &Crate> = ex1.crates.iter().map(|k| (ignore_key(k), k)).collect();
+    let after: BTreeMap<String, &Crate> = ex2.crates.iter().map(|k| (ignore_key(k), k)).collect();
+
+    let mut diffs = Vec::new();
+
+    for (key, krate) in &before {
+        if !after.contains_key(key) {
+            diffs.push(CrateDiff::Removed((*krate).clone()));
+        }
+    }
+
+    for (key, krate) in &after {
+        let before_krate = match before.get(key) {
+            Some(before_krate) => before_krate,
+            None => {
+                diffs.push(CrateDiff::Added((*krate).clone()));
+                continue;
+            }
+        };
+
+        if let Crate::Registry(RegistryCrate { version: ref v1, .. }) = **before_krate {
+            if let Crate::Registry(RegistryCrate { version: ref v2, .. }) = **krate {
+                if v1 != v2 {
+                    diffs.push(CrateDiff::VersionChanged {
+                        name: key.clone(),
+                        before: v1.clone(),
+                        after: v2.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    diffs
+}
+
+/// How a crate's build status (under an experiment's second, "experimental"
+/// toolchain) changed between two experiments, for crates present in both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusTransition {
+    Regressed,
+    Fixed,
+    StillBroken,
+    StillPassing,
+}
+
+/// The result of comparing two experiments: how their crate cohorts differ,
+/// and how shared crates' results moved between them.
+#[derive(Debug, Clone)]
+pub struct ExperimentDiff {
+    pub crates: Vec<CrateDiff>,
+    pub status_transitions: Vec<(String, StatusTransition)>,
+}
+
+/// Reduces a crate's recorded `TestResult` to pass/fail for the purposes of
+/// a status-transition diff. `BuildFail` and `TestFail` are both "broken";
+/// `TestSkipped` isn't a real outcome (the crate was never actually run), so
+/// it's excluded from the comparison entirely rather than counted either way.
+fn passed(result: &TestResult) -> Option<bool> {
+    match *result {
+        TestResult::TestPass => Some(true),
+        TestResult::BuildFail | TestResult::TestFail => Some(false),
+        TestResult::TestSkipped => None,
+    }
+}
+
+/// Compares two experiments' crate lists and, for crates present in both,
+/// the results recorded for them, so a cohort re-run after a compiler or
+/// crate-list change can be summarized as a delta rather than two full
+/// reports.
+pub fn diff<DB: ReadResults>(ex1_name: &str, ex2_name: &str, db: &DB) -> Result<ExperimentDiff> {
+    let ex1 = Experiment::load(ex1_name)?;
+    let ex2 = Experiment::load(ex2_name)?;
+
+    let crates = diff_crate_lists(&ex1, &ex2);
+
+    let ex1_crates: BTreeMap<String, &Crate> =
+        ex1.crates.iter().map(|k| (ignore_key(k), k)).collect();
+
+    let mut status_transitions = Vec::new();
+    for krate in &ex2.crates {
+        let key = ignore_key(krate);
+        let before_krate = match ex1_crates.get(&key) {
+            Some(before_krate) => *before_krate,
+            None => continue,
+        };
+
+        let before = db
+            .load_test_result(&ex1, &ex1.toolchains[1], before_krate)
+            .chain_err(|| format!("failed to read {} result for {}", ex1_name, key))?;
+        let after = db
+            .load_test_result(&ex2, &ex2.toolchains[1], krate)
+            .chain_err(|| format!("failed to read {} result for {}", ex2_name, key))?;
+
+        let transition = match (before.as_ref().and_then(passed), after.as_ref().and_then(passed)) {
+            (Some(true), Some(false)) => Some(StatusTransition::Regressed),
+            (Some(false), Some(true)) => Some(StatusTransition::Fixed),
+            (Some(false), Some(false)) => Some(StatusTransition::StillBroken),
+            (Some(true), Some(true)) => Some(StatusTransition::StillPassing),
+            (_, _) => None,
+        };
+
+        if let Some(transition) = transition {
+            status_transitions.push((key, transition));
+        }
+    }
+
+    Ok(ExperimentDiff {
+        crates,
+        status_transitions,
+    })
+}
+
 pub fn delete_all_target_dirs(ex_name: &str) -> Result<()> {
     let target_dir = &toolchain::ex_target_dir(ex_name);
     if target_dir.exists() {
@@ -366,6 +924,13 @@ pub fn delete(ex_name: &str) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::{ExCapLints, ExMode, Experiment};
+    use std::collections::HashSet;
+
+    fn write_temp_file(name: &str, contents: &str) -> ::std::path::PathBuf {
+        let path = ::std::env::temp_dir().join(format!("crater-test-{}-{}", ::std::process::id(), name));
+        ::file::write_string(&path, contents).unwrap();
+        path
+    }
 
     #[test]
     fn test_validate_experiment() {
@@ -378,6 +943,8 @@ mod tests {
                 mode: ExMode::BuildAndTest,
                 cap_lints: ExCapLints::Forbid,
                 rustflags: None,
+                ignore: HashSet::new(),
+                threads: 1,
             }.validate()
                 .is_ok()
         );
@@ -391,6 +958,8 @@ mod tests {
                 mode: ExMode::BuildAndTest,
                 cap_lints: ExCapLints::Forbid,
                 rustflags: None,
+                ignore: HashSet::new(),
+                threads: 1,
             }.validate()
                 .is_err()
         );
@@ -404,6 +973,8 @@ mod tests {
                 mode: ExMode::BuildAndTest,
                 cap_lints: ExCapLints::Forbid,
                 rustflags: Some("-Zfoo".into()),
+                ignore: HashSet::new(),
+                threads: 1,
             }.validate()
                 .is_err()
         );
@@ -417,8 +988,176 @@ mod tests {
                 mode: ExMode::BuildAndTest,
                 cap_lints: ExCapLints::Forbid,
                 rustflags: None,
+                ignore: HashSet::new(),
+                threads: 1,
             }.validate()
                 .is_err()
         );
     }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        use super::parse_diagnostics;
+
+        let lines = vec![
+            r#"{"reason":"compiler-message","message":{"level":"warning","code":{"code":"unused_variables"},"message":"unused variable","rendered":"warning: unused variable"}}"#.to_string(),
+            r#"{"reason":"compiler-message","message":{"level":"error","code":null,"message":"mismatched types","rendered":"error: mismatched types"}}"#.to_string(),
+            r#"{"reason":"compiler-message","message":{"level":"note","code":null,"message":"a note","rendered":"note: a note"}}"#.to_string(),
+            r#"{"reason":"build-script-executed"}"#.to_string(),
+        ];
+
+        let diagnostics = parse_diagnostics(&lines);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].code.as_ref().unwrap(), "unused_variables");
+        assert!(diagnostics[1].code.is_none());
+    }
+
+    #[test]
+    fn test_diff_diagnostics() {
+        use super::{diff_diagnostics, Diagnostic, DiagnosticLevel};
+
+        let before = vec![Diagnostic {
+            level: DiagnosticLevel::Warning,
+            code: Some("unused_variables".to_string()),
+            message: "unused variable".to_string(),
+        }];
+        let after = vec![
+            Diagnostic {
+                level: DiagnosticLevel::Warning,
+                code: Some("unused_variables".to_string()),
+                message: "unused variable".to_string(),
+            },
+            Diagnostic {
+                level: DiagnosticLevel::Warning,
+                code: Some("dead_code".to_string()),
+                message: "dead code".to_string(),
+            },
+        ];
+
+        let deltas = diff_diagnostics(&before, &after);
+        assert_eq!(deltas.len(), 1);
+        assert_eq!(deltas[0].code, "dead_code");
+        assert_eq!(deltas[0].before, 0);
+        assert_eq!(deltas[0].after, 1);
+        assert!(deltas[0].is_new());
+    }
+
+    #[test]
+    fn test_read_crate_list_file() {
+        use super::read_crate_list_file;
+        use crates::Crate;
+
+        let path = write_temp_file(
+            "crate-list.toml",
+            r#"
+            [crates.foo]
+            name = "foo"
+            version = "1.0.0"
+
+            [crates.bar]
+            github = "bar-org/bar"
+
+            [crates.baz]
+            path = "/tmp/baz"
+            "#,
+        );
+
+        let mut crates = read_crate_list_file(&path).unwrap();
+        crates.sort_by_key(|krate| krate.to_string());
+
+        assert_eq!(crates.len(), 3);
+        assert!(crates.iter().any(|krate| match *krate {
+            Crate::Registry(ref reg) => reg.name == "foo" && reg.version == "1.0.0",
+            _ => false,
+        }));
+        assert!(crates.iter().any(|krate| match *krate {
+            Crate::GitHub(ref repo) => repo.org == "bar-org" && repo.name == "bar",
+            _ => false,
+        }));
+        assert!(crates.iter().any(|krate| match *krate {
+            Crate::Local(ref path) => path == ::std::path::Path::new("/tmp/baz"),
+            _ => false,
+        }));
+
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_crate_list_file_invalid_github() {
+        use super::read_crate_list_file;
+
+        let path = write_temp_file(
+            "crate-list-invalid.toml",
+            r#"
+            [crates.bar]
+            github = "not-a-valid-slug"
+            "#,
+        );
+
+        assert!(read_crate_list_file(&path).is_err());
+
+        ::std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_diff_crate_lists() {
+        use super::{diff_crate_lists, CrateDiff};
+        use crates::{Crate, RegistryCrate};
+        use std::collections::HashSet;
+
+        fn registry(name: &str, version: &str) -> Crate {
+            Crate::Registry(RegistryCrate {
+                name: name.to_string(),
+                version: version.to_string(),
+            })
+        }
+
+        let ex1 = Experiment {
+            name: "ex1".to_string(),
+            crates: vec![
+                registry("foo", "1.0.0"),
+                registry("bar", "1.0.0"),
+            ],
+            toolchains: vec!["stable".parse().unwrap(), "beta".parse().unwrap()],
+            mode: ExMode::BuildAndTest,
+            cap_lints: ExCapLints::Forbid,
+            rustflags: None,
+            ignore: HashSet::new(),
+            threads: 1,
+        };
+
+        let ex2 = Experiment {
+            name: "ex2".to_string(),
+            crates: vec![
+                registry("foo", "1.1.0"),
+                registry("baz", "1.0.0"),
+            ],
+            toolchains: vec!["stable".parse().unwrap(), "beta".parse().unwrap()],
+            mode: ExMode::BuildAndTest,
+            cap_lints: ExCapLints::Forbid,
+            rustflags: None,
+            ignore: HashSet::new(),
+            threads: 1,
+        };
+
+        let diffs = diff_crate_lists(&ex1, &ex2);
+
+        assert_eq!(diffs.len(), 3);
+        assert!(diffs.iter().any(|diff| match *diff {
+            CrateDiff::Removed(Crate::Registry(RegistryCrate { ref name, .. })) => name == "bar",
+            _ => false,
+        }));
+        assert!(diffs.iter().any(|diff| match *diff {
+            CrateDiff::Added(Crate::Registry(RegistryCrate { ref name, .. })) => name == "baz",
+            _ => false,
+        }));
+        assert!(diffs.iter().any(|diff| match *diff {
+            CrateDiff::VersionChanged {
+                ref name,
+                ref before,
+                ref after,
+            } => name == "foo" && before == "1.0.0" && after == "1.1.0",
+            _ => false,
+        }));
+    }
 }